@@ -1,3 +1,4 @@
+#[cfg(not(target_os = "linux"))]
 use std::io::{BufRead, Cursor};
 use std::process::Output;
 use tokio::process::Command;
@@ -5,6 +6,34 @@ use tokio::process::Command;
 /// Compiled target triple, used as default for binary fetching
 pub const TARGET: &str = env!("TARGET");
 
+/// A target triple candidate returned by [`detect_targets`], in order of
+/// preference.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetTriple {
+    /// The target triple itself, e.g. `x86_64-unknown-linux-gnu`.
+    pub target: Box<str>,
+    /// The glibc version found on the host, if `target` is a `gnu` target
+    /// and the version could be determined. Fetchers can use this to skip
+    /// a prebuilt artifact whose declared minimum glibc is newer than what
+    /// the host provides, and fall through to the next candidate instead.
+    pub glibc_version: Option<(u32, u32)>,
+}
+
+impl TargetTriple {
+    fn new(target: Box<str>, glibc_version: Option<(u32, u32)>) -> Self {
+        Self {
+            target,
+            glibc_version,
+        }
+    }
+}
+
+impl From<&'static str> for TargetTriple {
+    fn from(target: &'static str) -> Self {
+        Self::new(target.into(), None)
+    }
+}
+
 /// Detect the targets supported at runtime,
 /// which might be different from `TARGET` which is detected
 /// at compile-time.
@@ -13,44 +42,168 @@ pub const TARGET: &str = env!("TARGET");
 /// If target_os is linux and it support gnu, then it is preferred
 /// to musl.
 ///
+/// On x86_64 linux hosts, gnu-hwcaps pseudo-targets such as
+/// `x86_64-unknown-linux-gnu-v3` are offered ahead of the plain gnu
+/// target, highest microarchitecture level first, so a fetcher can prefer
+/// an optimized artifact and fall back to the baseline build.
+///
 /// If target_os is mac and it is aarch64, then aarch64 is preferred
 /// to x86_64.
 ///
+/// If target_os is windows and the host is aarch64, then aarch64 is
+/// preferred to x86_64, since the x64 emulator can run x86_64 binaries.
+/// The `-gnu` variant of the detected triple is also offered as a
+/// lower-priority fallback, mirroring the gnu to musl fallback on Linux.
+///
 /// Check [this issue](https://github.com/ryankurte/cargo-binstall/issues/155)
 /// for more information.
-pub async fn detect_targets() -> Vec<Box<str>> {
-    if let Some(target) = get_target_from_rustc().await {
-        let mut v = vec![target];
+pub async fn detect_targets() -> Vec<TargetTriple> {
+    // Linux has its own full host detection (ELF/ldd-based libc flavor and
+    // version, hwcaps microarch level), all of which rustc's self-reported
+    // host triple can't provide, so it's used unconditionally rather than
+    // only when rustc is absent.
+    #[cfg(target_os = "linux")]
+    {
+        linux::detect_targets_linux().await
+    }
 
-        #[cfg(target_os = "linux")]
-        if v[0].contains("gnu") {
-            v.push(v[0].replace("gnu", "musl").into_boxed_str());
-        }
+    #[cfg(not(target_os = "linux"))]
+    {
+        if let Some(target) = get_target_from_rustc().await {
+            let mut v = vec![TargetTriple::new(target, None)];
+
+            #[cfg(target_os = "macos")]
+            if macos::is_arm64() && &*v[0].target != macos::AARCH64 {
+                v.insert(0, macos::AARCH64.into());
+            } else if &*v[0].target == macos::AARCH64 {
+                v.push(macos::X86.into());
+            }
 
-        #[cfg(target_os = "macos")]
-        if &*v[0] == macos::AARCH64 {
-            v.push(macos::X86.into());
+            #[cfg(target_os = "windows")]
+            {
+                // Capture what rustc actually reported before any
+                // insertion below changes `v[0]`, so the gnu fallback
+                // computed further down always mirrors rustc's answer
+                // rather than whatever ends up at the front of `v`.
+                let rustc_target = v[0].target.clone();
+
+                if &*rustc_target == windows::AARCH64_MSVC {
+                    // rustc directly reports the native arm64 triple;
+                    // still offer the x86_64 triple too, since the x64
+                    // emulator can run those binaries.
+                    v.push(windows::X86_64_MSVC.into());
+                } else if windows::is_native_arm64() && &*rustc_target == windows::X86_64_MSVC {
+                    // rustc (and the binstall binary) report x86_64, but
+                    // the machine is actually arm64, e.g. running under
+                    // emulation: prefer the native triple.
+                    v.insert(0, windows::AARCH64_MSVC.into());
+                }
+
+                if rustc_target.contains("msvc") {
+                    let gnu = rustc_target.replace("msvc", "gnu");
+                    v.push(TargetTriple::new(gnu.into_boxed_str(), None));
+                }
+            }
+
+            v
+        } else {
+            #[cfg(target_os = "macos")]
+            {
+                macos::detect_targets_macos()
+            }
+            #[cfg(target_os = "windows")]
+            {
+                windows::detect_targets_windows()
+            }
+            #[cfg(not(any(target_os = "macos", target_os = "windows")))]
+            {
+                vec![TargetTriple::new(TARGET.into(), None)]
+            }
         }
+    }
+}
 
-        v
+/// Known target triples that `--target`/`build.target` may be validated
+/// against. This isn't exhaustive: an unrecognized triple is still honored,
+/// just treated as opaque rather than specially cased.
+const KNOWN_TARGETS: &[&str] = &[
+    "x86_64-unknown-linux-gnu",
+    "x86_64-unknown-linux-musl",
+    "aarch64-unknown-linux-gnu",
+    "aarch64-unknown-linux-musl",
+    "x86_64-apple-darwin",
+    "aarch64-apple-darwin",
+    "x86_64-pc-windows-msvc",
+    "x86_64-pc-windows-gnu",
+    "aarch64-pc-windows-msvc",
+];
+
+/// Like [`detect_targets`], but honors explicitly requested targets ahead
+/// of host detection: `cli_targets` (from one or more `--target` options)
+/// takes priority, followed by the `build.target` key of the nearest
+/// `.cargo/config.toml` found by searching upward from `dir`. Only when
+/// neither is present does this fall back to [`detect_targets`].
+///
+/// Unlike auto-detected targets, explicitly requested ones are returned
+/// exactly as given, in the order given: no gnu/musl or aarch64/x86_64
+/// fallback expansion is added, since the user already said precisely what
+/// they want.
+pub async fn detect_targets_with_overrides(
+    cli_targets: Vec<String>,
+    dir: &std::path::Path,
+) -> Vec<TargetTriple> {
+    let explicit = if !cli_targets.is_empty() {
+        cli_targets
+    } else if let Some(target) = read_cargo_config_target(dir) {
+        vec![target]
     } else {
-        #[cfg(target_os = "linux")]
-        {
-            linux::detect_targets_linux().await
-        }
-        #[cfg(target_os = "macos")]
-        {
-            macos::detect_targets_macos()
-        }
-        #[cfg(not(any(target_os = "linux", target_os = "macos")))]
-        {
-            vec![TARGET.into()]
-        }
+        Vec::new()
+    };
+
+    if explicit.is_empty() {
+        detect_targets().await
+    } else {
+        explicit
+            .into_iter()
+            .map(|target| TargetTriple::new(validate_target(target), None))
+            .collect()
     }
 }
 
+/// Accept `target` as-is, whether or not it's one of [`KNOWN_TARGETS`]:
+/// an unrecognized triple is treated as opaque rather than rejected, since
+/// the user may simply be targeting a triple we don't special-case here.
+fn validate_target(target: String) -> Box<str> {
+    if !KNOWN_TARGETS.contains(&target.as_str()) {
+        eprintln!(
+            "warning: `{target}` is not a target cargo-binstall recognizes; using it as-is"
+        );
+    }
+
+    target.into_boxed_str()
+}
+
+/// Search upward from `dir` for the nearest `.cargo/config.toml` and
+/// return its `build.target` value, if set.
+fn read_cargo_config_target(dir: &std::path::Path) -> Option<String> {
+    let contents = dir.ancestors().find_map(|ancestor| {
+        std::fs::read_to_string(ancestor.join(".cargo").join("config.toml")).ok()
+    })?;
+    let config: toml::Value = contents.parse().ok()?;
+
+    config
+        .get("build")?
+        .get("target")?
+        .as_str()
+        .map(ToOwned::to_owned)
+}
+
 /// Figure out what the host target is using `rustc`.
 /// If `rustc` is absent, then it would return `None`.
+///
+/// Unused on Linux, which always runs its own full host detection instead
+/// (see [`detect_targets`]).
+#[cfg(not(target_os = "linux"))]
 async fn get_target_from_rustc() -> Option<Box<str>> {
     let Output { status, stdout, .. } = Command::new("rustc").arg("-vV").output().await.ok()?;
     if !status.success() {
@@ -68,49 +221,239 @@ async fn get_target_from_rustc() -> Option<Box<str>> {
 
 #[cfg(target_os = "linux")]
 mod linux {
-    use super::{Command, Output, TARGET};
+    use super::{Command, Output, TargetTriple, TARGET};
 
-    pub(super) async fn detect_targets_linux() -> Vec<Box<str>> {
+    /// The libc flavor detected on the host, together with the numeric
+    /// glibc version when it's a gnu host and the version could be parsed.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum LibcVersion {
+        Gnu(Option<(u32, u32)>),
+        Musl,
+    }
+
+    pub(super) async fn detect_targets_linux() -> Vec<TargetTriple> {
         let abi = parse_abi();
 
-        if let Ok(Output {
-            status: _,
-            stdout,
-            stderr,
-        }) = Command::new("ldd").arg("--version").output().await
-        {
-            let libc_version =
-                if let Some(libc_version) = parse_libc_version_from_ldd_output(&stdout) {
-                    libc_version
-                } else if let Some(libc_version) = parse_libc_version_from_ldd_output(&stderr) {
-                    libc_version
-                } else {
-                    return vec![create_target_str("musl", abi)];
-                };
-
-            if libc_version == "gnu" {
-                return vec![
-                    create_target_str("gnu", abi),
-                    create_target_str("musl", abi),
-                ];
+        let libc_version = match detect_libc_from_elf() {
+            // The ELF probe can tell gnu from musl, but since there's no
+            // version string in `PT_INTERP`, only a path, still ask `ldd`
+            // for the version number. This only costs a subprocess on gnu
+            // hosts, not on musl ones, where there's nothing to look up.
+            Some(LibcVersion::Gnu(None)) => {
+                LibcVersion::Gnu(detect_glibc_version_from_ldd().await)
             }
+            Some(libc_version) => libc_version,
+            None => match detect_libc_from_ldd().await {
+                Some(libc_version) => libc_version,
+                None => return vec![TargetTriple::new(create_target_str("musl", abi), None)],
+            },
+        };
+
+        if let LibcVersion::Gnu(glibc_version) = libc_version {
+            let gnu_target = create_target_str("gnu", abi);
+
+            let mut v = Vec::new();
+
+            #[cfg(target_arch = "x86_64")]
+            if let Some(level) = microarch::detect_level() {
+                // Highest-to-lowest, so the most optimized artifact is
+                // tried first and the plain gnu target remains the final,
+                // most compatible fallback.
+                for lvl in (2..=level).rev() {
+                    v.push(TargetTriple::new(
+                        format!("{gnu_target}-v{lvl}").into_boxed_str(),
+                        glibc_version,
+                    ));
+                }
+            }
+
+            v.push(TargetTriple::new(gnu_target, glibc_version));
+            v.push(TargetTriple::new(create_target_str("musl", abi), None));
+
+            return v;
         }
 
         // Fallback to using musl
-        vec![create_target_str("musl", abi)]
+        vec![TargetTriple::new(create_target_str("musl", abi), None)]
+    }
+
+    const PT_INTERP: u32 = 3;
+
+    /// The result of scanning an ELF file's program headers for
+    /// `PT_INTERP`, distinguishing "no such segment" (a statically linked
+    /// binary) from a segment that was found and whose dynamic linker path
+    /// is given.
+    #[derive(Debug, PartialEq, Eq)]
+    enum ElfInterp {
+        Found(String),
+        NotFound,
+    }
+
+    /// Detect the libc flavor from the `PT_INTERP` program header of the
+    /// currently running executable, without spawning a subprocess. This
+    /// works even in minimal containers and sandboxes that ship neither
+    /// `ldd` nor `rustc`.
+    ///
+    /// For a gnu host this returns `Some(LibcVersion::Gnu(None))`: the
+    /// glibc version is always left unset, since only the dynamic linker
+    /// path, not a version string, is available this way. It returns
+    /// `None` when the interpreter isn't recognized, or when the executable
+    /// couldn't be located or its ELF headers couldn't be parsed; callers
+    /// should then fall back to [`detect_libc_from_ldd`].
+    fn detect_libc_from_elf() -> Option<LibcVersion> {
+        let path = std::fs::read_link("/proc/self/exe")
+            .or_else(|_| std::env::current_exe())
+            .ok()?;
+        let data = std::fs::read(path).ok()?;
+
+        match find_elf_interp(&data)? {
+            ElfInterp::Found(interp) if interp.contains("ld-musl") => Some(LibcVersion::Musl),
+            ElfInterp::Found(interp)
+                if interp.contains("ld-linux") || interp.contains("ld.so") =>
+            {
+                Some(LibcVersion::Gnu(None))
+            }
+            // An interpreter we don't recognize: let the ldd fallback decide.
+            ElfInterp::Found(_) => None,
+            // No `PT_INTERP` segment at all: a fully static binary.
+            ElfInterp::NotFound => Some(LibcVersion::Musl),
+        }
+    }
+
+    /// Read the `PT_INTERP` segment of an ELF file, i.e. the path to its
+    /// dynamic linker, if it has one.
+    ///
+    /// Returns `None` if `data` couldn't be parsed as ELF at all (bad
+    /// magic, truncated headers, an unrecognized class/endianness, or an
+    /// out-of-bounds offset) so callers don't confuse a parse failure with
+    /// a genuinely static binary.
+    fn find_elf_interp(data: &[u8]) -> Option<ElfInterp> {
+        if data.len() < 64 || &data[0..4] != b"\x7fELF" {
+            return None;
+        }
+
+        let is_64 = match data[4] {
+            1 => false,
+            2 => true,
+            _ => return None,
+        };
+        let le = match data[5] {
+            1 => true,
+            2 => false,
+            _ => return None,
+        };
+
+        let read_u16 = |off: usize| -> Option<u16> {
+            data.get(off..off + 2).map(|b| {
+                let b = [b[0], b[1]];
+                if le {
+                    u16::from_le_bytes(b)
+                } else {
+                    u16::from_be_bytes(b)
+                }
+            })
+        };
+        let read_u32 = |off: usize| -> Option<u32> {
+            data.get(off..off + 4).map(|b| {
+                let b = [b[0], b[1], b[2], b[3]];
+                if le {
+                    u32::from_le_bytes(b)
+                } else {
+                    u32::from_be_bytes(b)
+                }
+            })
+        };
+        let read_u64 = |off: usize| -> Option<u64> {
+            data.get(off..off + 8).map(|b| {
+                let b: [u8; 8] = b.try_into().unwrap();
+                if le {
+                    u64::from_le_bytes(b)
+                } else {
+                    u64::from_be_bytes(b)
+                }
+            })
+        };
+
+        // e_phoff, e_phentsize, e_phnum live at different offsets for
+        // 32-bit vs 64-bit ELF headers.
+        let (e_phoff, e_phentsize, e_phnum) = if is_64 {
+            (
+                read_u64(32)?,
+                read_u16(54)? as u64,
+                read_u16(56)? as u64,
+            )
+        } else {
+            (
+                read_u32(28)? as u64,
+                read_u16(42)? as u64,
+                read_u16(44)? as u64,
+            )
+        };
+
+        for i in 0..e_phnum {
+            let ph_off = (e_phoff + i * e_phentsize) as usize;
+            let p_type = read_u32(ph_off)?;
+            if p_type != PT_INTERP {
+                continue;
+            }
+
+            let (p_offset, p_filesz) = if is_64 {
+                (read_u64(ph_off + 8)?, read_u64(ph_off + 32)?)
+            } else {
+                (read_u32(ph_off + 4)? as u64, read_u32(ph_off + 16)? as u64)
+            };
+
+            let start = p_offset as usize;
+            let end = start + p_filesz as usize;
+            let interp = data.get(start..end)?;
+            // Trim the trailing NUL terminator.
+            let interp = interp.split(|&b| b == 0).next()?;
+            return Some(ElfInterp::Found(String::from_utf8_lossy(interp).into_owned()));
+        }
+
+        Some(ElfInterp::NotFound)
+    }
+
+    async fn detect_libc_from_ldd() -> Option<LibcVersion> {
+        let Output { stdout, stderr, .. } =
+            Command::new("ldd").arg("--version").output().await.ok()?;
+
+        parse_libc_version_from_ldd_output(&stdout).or_else(|| parse_libc_version_from_ldd_output(&stderr))
     }
 
-    fn parse_libc_version_from_ldd_output(output: &[u8]) -> Option<&'static str> {
+    /// Fill in the numeric glibc version via `ldd --version`, for hosts
+    /// where the ELF probe already confirmed the flavor is gnu but had no
+    /// way to learn the version.
+    async fn detect_glibc_version_from_ldd() -> Option<(u32, u32)> {
+        match detect_libc_from_ldd().await? {
+            LibcVersion::Gnu(version) => version,
+            LibcVersion::Musl => None,
+        }
+    }
+
+    fn parse_libc_version_from_ldd_output(output: &[u8]) -> Option<LibcVersion> {
         let s = String::from_utf8_lossy(output);
+
         if s.contains("musl libc") {
-            Some("musl")
-        } else if s.contains("GLIBC") {
-            Some("gnu")
+            Some(LibcVersion::Musl)
+        } else if s.contains("GLIBC") || s.contains("GNU libc") {
+            let glibc_version = s
+                .split_whitespace()
+                .rev()
+                .find_map(parse_glibc_version_token);
+
+            Some(LibcVersion::Gnu(glibc_version))
         } else {
             None
         }
     }
 
+    /// Parse a token such as `2.31` into a `(major, minor)` pair.
+    fn parse_glibc_version_token(token: &str) -> Option<(u32, u32)> {
+        let (major, minor) = token.split_once('.')?;
+        Some((major.parse().ok()?, minor.parse().ok()?))
+    }
+
     fn parse_abi() -> &'static str {
         let last = TARGET.rsplit_once('-').unwrap().1;
 
@@ -134,20 +477,311 @@ mod linux {
 
         target.into_boxed_str()
     }
+
+    #[cfg(target_arch = "x86_64")]
+    mod microarch {
+        /// Highest x86-64 microarchitecture level (`x86-64-v2` to
+        /// `x86-64-v4`) fully supported by the host CPU, per the feature
+        /// sets defined by the System V ABI psABI, or `None` for a
+        /// baseline (`x86-64`/`v1`) host.
+        pub(super) fn detect_level() -> Option<u8> {
+            if has_v4() {
+                Some(4)
+            } else if has_v3() {
+                Some(3)
+            } else if has_v2() {
+                Some(2)
+            } else {
+                None
+            }
+        }
+
+        fn has_v2() -> bool {
+            std::is_x86_feature_detected!("cmpxchg16b")
+                && std::is_x86_feature_detected!("popcnt")
+                && std::is_x86_feature_detected!("sse3")
+                && std::is_x86_feature_detected!("ssse3")
+                && std::is_x86_feature_detected!("sse4.1")
+                && std::is_x86_feature_detected!("sse4.2")
+        }
+
+        fn has_v3() -> bool {
+            has_v2()
+                && std::is_x86_feature_detected!("avx")
+                && std::is_x86_feature_detected!("avx2")
+                && std::is_x86_feature_detected!("bmi1")
+                && std::is_x86_feature_detected!("bmi2")
+                && std::is_x86_feature_detected!("fma")
+                && std::is_x86_feature_detected!("movbe")
+                && std::is_x86_feature_detected!("f16c")
+                && std::is_x86_feature_detected!("lzcnt")
+        }
+
+        fn has_v4() -> bool {
+            has_v3()
+                && std::is_x86_feature_detected!("avx512f")
+                && std::is_x86_feature_detected!("avx512bw")
+                && std::is_x86_feature_detected!("avx512cd")
+                && std::is_x86_feature_detected!("avx512dq")
+                && std::is_x86_feature_detected!("avx512vl")
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn parses_glibc_version_token() {
+            assert_eq!(parse_glibc_version_token("2.31"), Some((2, 31)));
+            assert_eq!(parse_glibc_version_token("2.35"), Some((2, 35)));
+            assert_eq!(parse_glibc_version_token("2.31-0ubuntu9.9"), None);
+            assert_eq!(parse_glibc_version_token("v2.31"), None);
+            assert_eq!(parse_glibc_version_token(""), None);
+        }
+
+        #[test]
+        fn parses_ldd_output_ubuntu() {
+            // Real `ldd --version` output, including the multi-line
+            // copyright text the reverse token scan has to skip past.
+            let output = b"ldd (Ubuntu GLIBC 2.31-0ubuntu9.9) 2.31\n\
+Copyright (C) 2020 Free Software Foundation, Inc.\n\
+This is free software; see the source for copying conditions.  There is NO\n\
+warranty; not even for MERCHANTABILITY or FITNESS FOR A PARTICULAR PURPOSE.\n\
+Written by Roland McGrath and Ulrich Drepper.\n";
+            assert_eq!(
+                parse_libc_version_from_ldd_output(output),
+                Some(LibcVersion::Gnu(Some((2, 31))))
+            );
+        }
+
+        #[test]
+        fn parses_ldd_output_gnu_libc() {
+            let output = b"ldd (GNU libc) 2.35\n\
+Copyright (C) 2022 Free Software Foundation, Inc.\n\
+This is free software; see the source for copying conditions.\n";
+            assert_eq!(
+                parse_libc_version_from_ldd_output(output),
+                Some(LibcVersion::Gnu(Some((2, 35))))
+            );
+        }
+
+        #[test]
+        fn parses_ldd_output_musl() {
+            let output = b"musl libc (x86_64)\nVersion 1.2.3\nDynamic Program Loader\n";
+            assert_eq!(
+                parse_libc_version_from_ldd_output(output),
+                Some(LibcVersion::Musl)
+            );
+        }
+
+        #[test]
+        fn parses_ldd_output_unrecognized() {
+            assert_eq!(
+                parse_libc_version_from_ldd_output(b"not ldd output at all"),
+                None
+            );
+        }
+
+        /// Build a minimal synthetic ELF byte buffer with an optional
+        /// `PT_INTERP` segment, for exercising [`find_elf_interp`] without
+        /// needing a real binary on disk.
+        fn build_elf(is_64: bool, interp: Option<&[u8]>) -> Vec<u8> {
+            let mut data = vec![0u8; 64];
+            data[0..4].copy_from_slice(b"\x7fELF");
+            data[4] = if is_64 { 2 } else { 1 };
+            data[5] = 1; // little-endian
+
+            let phoff = data.len() as u64;
+            let phentsize: u16 = if is_64 { 56 } else { 32 };
+            let phnum: u16 = u16::from(interp.is_some());
+
+            if is_64 {
+                data[32..40].copy_from_slice(&phoff.to_le_bytes());
+                data[54..56].copy_from_slice(&phentsize.to_le_bytes());
+                data[56..58].copy_from_slice(&phnum.to_le_bytes());
+            } else {
+                data[28..32].copy_from_slice(&(phoff as u32).to_le_bytes());
+                data[42..44].copy_from_slice(&phentsize.to_le_bytes());
+                data[44..46].copy_from_slice(&phnum.to_le_bytes());
+            }
+
+            if let Some(interp) = interp {
+                let p_offset = (data.len() + phentsize as usize) as u64;
+                let p_filesz = interp.len() as u64;
+
+                let mut ph = vec![0u8; phentsize as usize];
+                ph[0..4].copy_from_slice(&PT_INTERP.to_le_bytes());
+                if is_64 {
+                    ph[8..16].copy_from_slice(&p_offset.to_le_bytes());
+                    ph[32..40].copy_from_slice(&p_filesz.to_le_bytes());
+                } else {
+                    ph[4..8].copy_from_slice(&(p_offset as u32).to_le_bytes());
+                    ph[16..20].copy_from_slice(&(p_filesz as u32).to_le_bytes());
+                }
+                data.extend_from_slice(&ph);
+                data.extend_from_slice(interp);
+            }
+
+            data
+        }
+
+        #[test]
+        fn finds_interp_elf64_gnu() {
+            let data = build_elf(true, Some(b"/lib64/ld-linux-x86-64.so.2"));
+            assert_eq!(
+                find_elf_interp(&data),
+                Some(ElfInterp::Found("/lib64/ld-linux-x86-64.so.2".to_owned()))
+            );
+        }
+
+        #[test]
+        fn finds_interp_elf32_gnu() {
+            let data = build_elf(false, Some(b"/lib/ld-linux.so.2"));
+            assert_eq!(
+                find_elf_interp(&data),
+                Some(ElfInterp::Found("/lib/ld-linux.so.2".to_owned()))
+            );
+        }
+
+        #[test]
+        fn finds_interp_musl() {
+            let data = build_elf(true, Some(b"/lib/ld-musl-x86_64.so.1"));
+            assert_eq!(
+                find_elf_interp(&data),
+                Some(ElfInterp::Found("/lib/ld-musl-x86_64.so.1".to_owned()))
+            );
+        }
+
+        #[test]
+        fn finds_no_interp_static_binary() {
+            let data = build_elf(true, None);
+            assert_eq!(find_elf_interp(&data), Some(ElfInterp::NotFound));
+        }
+
+        #[test]
+        fn rejects_truncated_or_corrupt_elf() {
+            assert_eq!(find_elf_interp(&[]), None);
+            assert_eq!(find_elf_interp(b"\x7fELF\x02\x01"), None);
+            assert_eq!(find_elf_interp(b"not an elf file at all, too short"), None);
+
+            // Valid-looking header but an unrecognized EI_CLASS byte.
+            let mut data = build_elf(true, Some(b"/lib64/ld-linux-x86-64.so.2"));
+            data[4] = 0xff;
+            assert_eq!(find_elf_interp(&data), None);
+        }
+    }
 }
 
 #[cfg(target_os = "macos")]
 mod macos {
-    use guess_host_triple::guess_host_triple;
+    use std::ffi::CString;
+    use std::os::raw::c_void;
+    use std::ptr;
 
     pub(super) const AARCH64: &str = "aarch64-apple-darwin";
     pub(super) const X86: &str = "x86_64-apple-darwin";
 
-    pub(super) fn detect_targets_macos() -> Vec<Box<str>> {
-        if guess_host_triple() == Some(AARCH64) {
+    pub(super) fn detect_targets_macos() -> Vec<super::TargetTriple> {
+        if is_arm64() {
             vec![AARCH64.into(), X86.into()]
         } else {
             vec![X86.into()]
         }
     }
-}
\ No newline at end of file
+
+    /// Whether the host is Apple Silicon, determined via `sysctlbyname`
+    /// rather than `guess_host_triple` (which reports the *process's*
+    /// architecture). This way, a binstall binary built for x86_64 and
+    /// running under Rosetta 2 on Apple Silicon still discovers that the
+    /// host can run native `aarch64-apple-darwin` artifacts.
+    pub(super) fn is_arm64() -> bool {
+        // Set when the current process is translated, i.e. running under
+        // Rosetta 2.
+        sysctl_bool("sysctl.proc_translated")
+            // Set on Apple Silicon hardware, regardless of translation.
+            || sysctl_bool("hw.optional.arm64")
+    }
+
+    fn sysctl_bool(name: &str) -> bool {
+        let Ok(name) = CString::new(name) else {
+            return false;
+        };
+
+        let mut value: i32 = 0;
+        let mut size = std::mem::size_of::<i32>();
+
+        let ret = unsafe {
+            libc::sysctlbyname(
+                name.as_ptr(),
+                &mut value as *mut i32 as *mut c_void,
+                &mut size,
+                ptr::null_mut(),
+                0,
+            )
+        };
+
+        ret == 0 && value == 1
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod windows {
+    use windows_sys::Win32::Foundation::HANDLE;
+    use windows_sys::Win32::System::SystemInformation::{
+        GetNativeSystemInfo, PROCESSOR_ARCHITECTURE_ARM64, SYSTEM_INFO,
+    };
+    use windows_sys::Win32::System::Threading::{GetCurrentProcess, IsWow64Process2};
+
+    pub(super) const AARCH64_MSVC: &str = "aarch64-pc-windows-msvc";
+    pub(super) const X86_64_MSVC: &str = "x86_64-pc-windows-msvc";
+
+    pub(super) fn detect_targets_windows() -> Vec<super::TargetTriple> {
+        let mut v = if is_native_arm64() {
+            vec![AARCH64_MSVC.into(), X86_64_MSVC.into()]
+        } else {
+            vec![X86_64_MSVC.into()]
+        };
+
+        // Offer the -gnu variant of the top (native) triple as a
+        // lower-priority fallback, mirroring the gnu->musl fallback used
+        // on Linux.
+        let gnu = v[0].target.replace("msvc", "gnu");
+        v.push(super::TargetTriple::new(gnu.into_boxed_str(), None));
+
+        v
+    }
+
+    /// Determine whether the *machine* is arm64, as opposed to the current
+    /// process which may be x86_64 running under emulation. Prefers
+    /// `IsWow64Process2` (available since Windows 10 1511) and falls back
+    /// to `GetNativeSystemInfo` if that API call fails.
+    pub(super) fn is_native_arm64() -> bool {
+        is_native_arm64_via_wow64process2().unwrap_or_else(is_native_arm64_via_system_info)
+    }
+
+    fn is_native_arm64_via_wow64process2() -> Option<bool> {
+        const IMAGE_FILE_MACHINE_ARM64: u16 = 0xaa64;
+
+        let mut process_machine = 0u16;
+        let mut native_machine = 0u16;
+
+        let ret = unsafe {
+            IsWow64Process2(
+                GetCurrentProcess() as HANDLE,
+                &mut process_machine,
+                &mut native_machine,
+            )
+        };
+
+        (ret != 0).then_some(native_machine == IMAGE_FILE_MACHINE_ARM64)
+    }
+
+    fn is_native_arm64_via_system_info() -> bool {
+        unsafe {
+            let mut info: SYSTEM_INFO = std::mem::zeroed();
+            GetNativeSystemInfo(&mut info);
+            info.Anonymous.Anonymous.wProcessorArchitecture == PROCESSOR_ARCHITECTURE_ARM64
+        }
+    }
+}